@@ -0,0 +1,291 @@
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::future::{self, Shared};
+use futures::{BoxFuture, Future};
+
+use Resolver;
+
+/// A `Resolver` decorator that memoizes results of an inner resolver
+/// for a configurable time-to-live.
+///
+/// Successful lookups are cached for `positive_ttl`; failed lookups
+/// are cached for the (usually shorter) `negative_ttl`, so that a
+/// host which is temporarily unresolvable doesn't hammer the inner
+/// resolver. Concurrent lookups for the same host in flight at the
+/// same time are coalesced into a single call to the inner resolver.
+/// The cache holds at most `capacity` entries, evicting the least
+/// recently used one once full.
+pub struct CachedResolver<R> {
+    inner: Arc<Inner<R>>,
+}
+
+struct Inner<R> {
+    resolver: R,
+    positive_ttl: Duration,
+    negative_ttl: Duration,
+    capacity: usize,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<String, Entry>,
+    lru: VecDeque<String>,
+    in_flight: HashMap<String, Shared<BoxFuture<Vec<IpAddr>, CacheError>>>,
+}
+
+struct Entry {
+    result: Result<Vec<IpAddr>, CacheError>,
+    expires_at: Instant,
+}
+
+#[derive(Clone)]
+struct CacheError(Arc<io::Error>);
+
+impl From<CacheError> for io::Error {
+    fn from(e: CacheError) -> io::Error {
+        io::Error::new(e.0.kind(), format!("{}", e.0))
+    }
+}
+
+impl State {
+    fn touch(&mut self, host: &str) {
+        if let Some(pos) = self.lru.iter().position(|h| h == host) {
+            self.lru.remove(pos);
+        }
+        self.lru.push_front(host.to_owned());
+    }
+
+    fn insert(&mut self, host: String, entry: Entry, capacity: usize) {
+        self.touch(&host);
+        self.entries.insert(host, entry);
+        while self.entries.len() > capacity {
+            if let Some(oldest) = self.lru.pop_back() {
+                self.entries.remove(&oldest);
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+impl<R: Resolver> CachedResolver<R> {
+    /// Wraps `resolver`, caching positive results for `positive_ttl`
+    /// and negative (error) results for `negative_ttl`, keeping at
+    /// most `capacity` entries.
+    pub fn new(resolver: R, positive_ttl: Duration, negative_ttl: Duration, capacity: usize) -> Self {
+        CachedResolver {
+            inner: Arc::new(Inner {
+                resolver: resolver,
+                positive_ttl: positive_ttl,
+                negative_ttl: negative_ttl,
+                capacity: capacity,
+                state: Mutex::new(State::default()),
+            }),
+        }
+    }
+}
+
+impl<R> Clone for CachedResolver<R> {
+    fn clone(&self) -> Self {
+        CachedResolver { inner: self.inner.clone() }
+    }
+}
+
+impl<R: Resolver + Send + Sync + 'static> Resolver for CachedResolver<R> {
+    fn resolve(&self, host: &str) -> BoxFuture<Vec<IpAddr>, io::Error> {
+        let now = Instant::now();
+        let mut state = self.inner.state.lock().unwrap();
+
+        let fresh = state.entries.get(host).and_then(|entry| {
+            if entry.expires_at > now {
+                Some(entry.result.clone())
+            } else {
+                None
+            }
+        });
+        if let Some(result) = fresh {
+            state.touch(host);
+            return match result {
+                Ok(ips) => future::ok(ips).boxed(),
+                Err(e) => future::err(e.into()).boxed(),
+            };
+        }
+
+        if let Some(shared) = state.in_flight.get(host) {
+            return shared.clone().then(|res| {
+                match res {
+                    Ok(ips) => Ok((*ips).clone()),
+                    Err(e) => Err((*e).clone().into()),
+                }
+            }).boxed();
+        }
+
+        let host = host.to_owned();
+        let inner = self.inner.clone();
+        let fut: BoxFuture<Vec<IpAddr>, CacheError> = self.inner.resolver.resolve(&host)
+            .map_err(|e| CacheError(Arc::new(e)))
+            .boxed();
+        let shared = fut.shared();
+
+        state.in_flight.insert(host.clone(), shared.clone());
+        drop(state);
+
+        shared.then(move |res| {
+            let mut state = inner.state.lock().unwrap();
+            state.in_flight.remove(&host);
+
+            match res {
+                Ok(ips) => {
+                    let ips = (*ips).clone();
+                    state.insert(host, Entry {
+                        result: Ok(ips.clone()),
+                        expires_at: Instant::now() + inner.positive_ttl,
+                    }, inner.capacity);
+                    Ok(ips)
+                }
+                Err(e) => {
+                    let e = (*e).clone();
+                    state.insert(host, Entry {
+                        result: Err(e.clone()),
+                        expires_at: Instant::now() + inner.negative_ttl,
+                    }, inner.capacity);
+                    Err(e.into())
+                }
+            }
+        }).boxed()
+    }
+
+    fn resolve_reverse(&self, ip: IpAddr) -> BoxFuture<Vec<String>, io::Error> {
+        // Reverse lookups are not cached; they are much less
+        // frequently repeated than forward lookups in practice.
+        self.inner.resolver.resolve_reverse(ip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CachedResolver;
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use futures::future;
+    use futures::{BoxFuture, Future};
+    use std::io;
+    use Resolver;
+
+    struct CountingResolver {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Resolver for CountingResolver {
+        fn resolve(&self, host: &str) -> BoxFuture<Vec<IpAddr>, io::Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if host == "fail" {
+                future::err(io::Error::new(io::ErrorKind::Other, "boom")).boxed()
+            } else {
+                future::ok(vec![IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))]).boxed()
+            }
+        }
+    }
+
+    /// A resolver that counts calls and blocks the polling thread for
+    /// `delay` before resolving, so tests can force two lookups to be
+    /// in flight at the same time.
+    struct SlowResolver {
+        calls: Arc<AtomicUsize>,
+        delay: Duration,
+    }
+
+    impl Resolver for SlowResolver {
+        fn resolve(&self, _host: &str) -> BoxFuture<Vec<IpAddr>, io::Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let delay = self.delay;
+            future::lazy(move || {
+                thread::sleep(delay);
+                future::ok(vec![IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))])
+            }).boxed()
+        }
+    }
+
+    #[test]
+    fn test_negative_ttl_expires() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let resolver = CachedResolver::new(
+            CountingResolver { calls: calls.clone() },
+            Duration::from_secs(60),
+            Duration::from_millis(20),
+            10,
+        );
+
+        assert!(resolver.resolve("fail").wait().is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Still within the negative TTL: served from cache.
+        assert!(resolver.resolve("fail").wait().is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        thread::sleep(Duration::from_millis(40));
+
+        // Negative entry has expired: the inner resolver is hit again.
+        assert!(resolver.resolve("fail").wait().is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_lru_eviction_at_capacity() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let resolver = CachedResolver::new(
+            CountingResolver { calls: calls.clone() },
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            2,
+        );
+
+        resolver.resolve("a").wait().unwrap();
+        resolver.resolve("b").wait().unwrap();
+        resolver.resolve("c").wait().unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // "a" was evicted to make room for "c": resolving it again
+        // misses the cache and hits the inner resolver.
+        resolver.resolve("a").wait().unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        // "c" is still cached.
+        resolver.resolve("c").wait().unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_coalesces_concurrent_lookups() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let resolver = CachedResolver::new(
+            SlowResolver { calls: calls.clone(), delay: Duration::from_millis(100) },
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            10,
+        );
+
+        let first = {
+            let resolver = resolver.clone();
+            thread::spawn(move || resolver.resolve("host").wait().unwrap())
+        };
+
+        // Give the first lookup time to register itself in `in_flight`
+        // before this thread's lookup for the same host starts.
+        thread::sleep(Duration::from_millis(20));
+        let second = resolver.resolve("host").wait().unwrap();
+
+        assert_eq!(first.join().unwrap(), second);
+        // Both lookups were coalesced into a single call to the inner resolver.
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::VecDeque;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures::future::{self, Either};
+use futures::Future;
+use tokio_core::net::{TcpListener, TcpStream, UdpSocket};
+use tokio_core::reactor::{Handle, Timeout};
+
+use {Resolver, ToEndpoint};
+
+/// Default delay before starting a concurrent connection attempt
+/// to the next candidate address, as recommended by RFC 8305.
+const DEFAULT_ATTEMPT_DELAY_MS: u64 = 250;
+
+/// A boxed future returned by the connection helpers in this module.
+///
+/// These futures hold a `tokio_core::reactor::Handle`, which is not
+/// `Send` (the reactor is inherently single-threaded; `Remote` is its
+/// `Send` cross-thread handle). That rules out the crate's
+/// `futures::BoxFuture`, which requires `Send`, so this module uses
+/// its own non-`Send` boxed-future alias instead.
+pub type ConnectFuture<T> = Box<Future<Item = T, Error = io::Error>>;
+
+/// Options controlling how [`tcp_connect_with`](fn.tcp_connect_with.html)
+/// races candidate addresses.
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectOptions {
+    /// Delay between the start of successive connection attempts.
+    pub attempt_delay: Duration,
+    /// If `true`, addresses are tried one at a time instead of being
+    /// raced with the Happy Eyeballs strategy.
+    pub sequential: bool,
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        ConnectOptions {
+            attempt_delay: Duration::from_millis(DEFAULT_ATTEMPT_DELAY_MS),
+            sequential: false,
+        }
+    }
+}
+
+/// Resolves `endpoint` and connects to it using a Happy Eyeballs
+/// (RFC 8305) dual-stack racing strategy with the default options.
+pub fn tcp_connect<'a, R, E>(resolver: &R, endpoint: E, handle: &Handle) -> ConnectFuture<TcpStream>
+    where R: Resolver, E: ToEndpoint<'a>
+{
+    tcp_connect_with(resolver, endpoint, handle, ConnectOptions::default())
+}
+
+/// Like [`tcp_connect`](fn.tcp_connect.html), but with explicit
+/// control over the racing strategy via `opts`.
+pub fn tcp_connect_with<'a, R, E>(resolver: &R, endpoint: E, handle: &Handle, opts: ConnectOptions) -> ConnectFuture<TcpStream>
+    where R: Resolver, E: ToEndpoint<'a>
+{
+    let ep = match endpoint.to_endpoint() {
+        Ok(ep) => ep,
+        Err(e) => return Box::new(future::err(e)),
+    };
+    let handle = handle.clone();
+    Box::new(resolver.resolve_endpoint(ep).and_then(move |addrs| {
+        if opts.sequential {
+            try_until_ok(addrs, handle)
+        } else {
+            connect_happy_eyeballs(happy_eyeballs_order(addrs), handle, opts.attempt_delay)
+        }
+    }))
+}
+
+/// Resolves `endpoint` and binds a `TcpListener` to the first
+/// resolved address.
+pub fn tcp_listen<'a, R, E>(resolver: &R, endpoint: E, handle: &Handle) -> ConnectFuture<TcpListener>
+    where R: Resolver, E: ToEndpoint<'a>
+{
+    let ep = match endpoint.to_endpoint() {
+        Ok(ep) => ep,
+        Err(e) => return Box::new(future::err(e)),
+    };
+    let handle = handle.clone();
+    Box::new(resolver.resolve_endpoint(ep).and_then(move |addrs| {
+        first_addr(addrs).and_then(|addr| TcpListener::bind(&addr, &handle))
+    }))
+}
+
+/// Resolves `endpoint` and binds a `UdpSocket` to the first
+/// resolved address.
+pub fn udp_bind<'a, R, E>(resolver: &R, endpoint: E, handle: &Handle) -> ConnectFuture<UdpSocket>
+    where R: Resolver, E: ToEndpoint<'a>
+{
+    let ep = match endpoint.to_endpoint() {
+        Ok(ep) => ep,
+        Err(e) => return Box::new(future::err(e)),
+    };
+    let handle = handle.clone();
+    Box::new(resolver.resolve_endpoint(ep).and_then(move |addrs| {
+        first_addr(addrs).and_then(|addr| UdpSocket::bind(&addr, &handle))
+    }))
+}
+
+/// Tries to connect to each address in `addrs` in order, one at a
+/// time, returning the first successful connection. If every address
+/// fails, the last error encountered is returned.
+pub fn try_until_ok(addrs: Vec<SocketAddr>, handle: Handle) -> ConnectFuture<TcpStream> {
+    let mut addrs = VecDeque::from(addrs);
+    match addrs.pop_front() {
+        None => Box::new(future::err(no_addresses_error())),
+        Some(addr) => {
+            Box::new(TcpStream::connect(&addr, &handle).or_else(move |e| {
+                if addrs.is_empty() {
+                    Box::new(future::err(e)) as ConnectFuture<TcpStream>
+                } else {
+                    try_until_ok(addrs.into(), handle)
+                }
+            }))
+        }
+    }
+}
+
+/// Races connection attempts to `addrs` using the Happy Eyeballs
+/// strategy: a new attempt is started every `delay` without
+/// cancelling the previous ones, and the first to succeed wins.
+fn connect_happy_eyeballs(addrs: Vec<SocketAddr>, handle: Handle, delay: Duration) -> ConnectFuture<TcpStream> {
+    let mut addrs = VecDeque::from(addrs);
+    match addrs.pop_front() {
+        None => Box::new(future::err(no_addresses_error())),
+        Some(addr) => connect_one(addr, addrs, handle, delay),
+    }
+}
+
+fn connect_one(addr: SocketAddr, rest: VecDeque<SocketAddr>, handle: Handle, delay: Duration) -> ConnectFuture<TcpStream> {
+    let connect = TcpStream::connect(&addr, &handle);
+
+    if rest.is_empty() {
+        return Box::new(connect);
+    }
+
+    let timeout = match Timeout::new(delay, &handle) {
+        Ok(timeout) => timeout,
+        // Can't schedule a timer: fall back to a purely sequential attempt.
+        Err(_) => return Box::new(connect.or_else({
+            let handle = handle.clone();
+            move |_| connect_happy_eyeballs(rest.into(), handle, delay)
+        })),
+    };
+
+    Box::new(connect.select2(timeout).then(move |res| -> ConnectFuture<TcpStream> {
+        match res {
+            // Connected before the attempt delay elapsed.
+            Ok(Either::A((stream, _))) => Box::new(future::ok(stream)),
+            // Failed before the attempt delay elapsed: move on immediately.
+            Err(Either::A((e, _))) => {
+                if rest.is_empty() {
+                    Box::new(future::err(e))
+                } else {
+                    connect_happy_eyeballs(rest.into(), handle, delay)
+                }
+            }
+            // The attempt delay elapsed first: start the next candidate
+            // concurrently without giving up on this one.
+            Ok(Either::B((_, pending_connect))) => {
+                let next = connect_happy_eyeballs(rest.into(), handle, delay);
+                Box::new(future::select_ok(vec![Box::new(pending_connect) as ConnectFuture<TcpStream>, next])
+                    .map(|(stream, _)| stream))
+            }
+            // The timer itself failed; just keep waiting on this attempt.
+            Err(Either::B((_, pending_connect))) => Box::new(pending_connect),
+        }
+    }))
+}
+
+/// Sorts resolved addresses so that address families alternate,
+/// starting with IPv6 whenever any IPv6 address is present (IPv6 and
+/// IPv4 hosts are interleaved rather than tried strictly in resolution
+/// order; within each family the resolution order is kept).
+fn happy_eyeballs_order(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let mut v6 = VecDeque::new();
+    let mut v4 = VecDeque::new();
+    for addr in addrs {
+        if addr.is_ipv6() {
+            v6.push_back(addr);
+        } else {
+            v4.push_back(addr);
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(v6.len() + v4.len());
+    loop {
+        match (v6.pop_front(), v4.pop_front()) {
+            (None, None) => break,
+            (Some(a), Some(b)) => { ordered.push(a); ordered.push(b); }
+            (Some(a), None) => ordered.push(a),
+            (None, Some(b)) => ordered.push(b),
+        }
+    }
+    ordered
+}
+
+fn first_addr(addrs: Vec<SocketAddr>) -> Result<SocketAddr, io::Error> {
+    addrs.into_iter().next().ok_or_else(no_addresses_error)
+}
+
+fn no_addresses_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "no addresses to connect to")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::happy_eyeballs_order;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+    fn v4(a: u8, b: u8, c: u8, d: u8, port: u16) -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(a, b, c, d)), port)
+    }
+
+    fn v6(segment: u16, port: u16) -> SocketAddr {
+        SocketAddr::new(IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, segment)), port)
+    }
+
+    #[test]
+    fn test_happy_eyeballs_order_interleaves_families() {
+        let addrs = vec![v4(1, 1, 1, 1, 80), v4(2, 2, 2, 2, 80), v6(1, 80), v6(2, 80)];
+        let ordered = happy_eyeballs_order(addrs);
+        assert_eq!(ordered, vec![v6(1, 80), v4(1, 1, 1, 1, 80), v6(2, 80), v4(2, 2, 2, 2, 80)]);
+    }
+
+    #[test]
+    fn test_happy_eyeballs_order_single_family() {
+        let addrs = vec![v4(1, 1, 1, 1, 80), v4(2, 2, 2, 2, 80)];
+        let ordered = happy_eyeballs_order(addrs);
+        assert_eq!(ordered, vec![v4(1, 1, 1, 1, 80), v4(2, 2, 2, 2, 80)]);
+    }
+
+    #[test]
+    fn test_happy_eyeballs_order_more_of_one_family() {
+        let addrs = vec![v6(1, 80), v4(1, 1, 1, 1, 80), v4(2, 2, 2, 2, 80)];
+        let ordered = happy_eyeballs_order(addrs);
+        assert_eq!(ordered, vec![v6(1, 80), v4(1, 1, 1, 1, 80), v4(2, 2, 2, 2, 80)]);
+    }
+}
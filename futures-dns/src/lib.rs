@@ -1,19 +1,63 @@
 extern crate futures;
 extern crate futures_cpupool;
+extern crate libc;
+extern crate tokio_core;
+#[cfg(feature = "tower")]
+extern crate tower_service;
 
 use std::io;
 use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::str::FromStr;
 
-use futures::{BoxFuture, Future};
+use futures::{future, BoxFuture, Future};
 use futures_cpupool::CpuPool;
 
+mod cached;
+mod connect;
+mod reverse;
+mod router;
+#[cfg(feature = "tower")]
+mod service;
+
+pub use cached::CachedResolver;
+pub use connect::{tcp_connect, tcp_connect_with, tcp_listen, udp_bind, try_until_ok, ConnectFuture, ConnectOptions};
+pub use reverse::resolve_ip_addr;
+pub use router::{RouterResolver, RouterResolverBuilder};
+#[cfg(feature = "tower")]
+pub use service::{Name, ResolverService};
+
 /// The Resolver trait represents an object capable of
 /// resolving host names into IP addresses.
 pub trait Resolver {
     /// Given a host name, this function returns a Future which
     /// will eventually resolve into a list of IP addresses.
     fn resolve(&self, host: &str) -> BoxFuture<Vec<IpAddr>, io::Error>;
+
+    /// Resolves `ep` into one or more `SocketAddr`s, preserving its port.
+    ///
+    /// `Endpoint::SocketAddr` is returned as-is; `Endpoint::Host` is
+    /// resolved via `resolve` and each resulting `IpAddr` is combined
+    /// with the endpoint's port.
+    fn resolve_endpoint<'a>(&self, ep: Endpoint<'a>) -> BoxFuture<Vec<SocketAddr>, io::Error> {
+        match ep {
+            Endpoint::SocketAddr(addr) => future::ok(vec![addr]).boxed(),
+            Endpoint::Host(host, port) => {
+                self.resolve(host).map(move |ips| {
+                    ips.into_iter().map(|ip| SocketAddr::new(ip, port)).collect()
+                }).boxed()
+            }
+        }
+    }
+
+    /// Resolves `ip` back into one or more host names.
+    ///
+    /// The default implementation reports that this resolver does not
+    /// support reverse resolution; resolvers backed by the platform's
+    /// facilities (e.g. `CpuPoolResolver`) override it.
+    fn resolve_reverse(&self, ip: IpAddr) -> BoxFuture<Vec<String>, io::Error> {
+        let _ = ip;
+        future::err(io::Error::new(io::ErrorKind::Other, "reverse resolution not supported by this resolver")).boxed()
+    }
 }
 
 /// A resolver based on a thread pool.
@@ -47,6 +91,10 @@ impl Resolver for CpuPoolResolver {
             res.unwrap()
         }).boxed()
     }
+
+    fn resolve_reverse(&self, ip: IpAddr) -> BoxFuture<Vec<String>, io::Error> {
+        resolve_ip_addr(&self.pool, ip)
+    }
 }
 
 /// An Endpoint is a way of identifying the target of a connection.
@@ -146,3 +194,46 @@ fn test_endpoint_str() {
         _ => panic!(),
     }
 }
+
+struct StubResolver(Vec<IpAddr>);
+
+impl Resolver for StubResolver {
+    fn resolve(&self, _host: &str) -> BoxFuture<Vec<IpAddr>, io::Error> {
+        future::ok(self.0.clone()).boxed()
+    }
+}
+
+#[test]
+fn test_resolve_endpoint_host_pairs_ips_with_port() {
+    use std::net::Ipv4Addr;
+
+    let resolver = StubResolver(vec![
+        IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)),
+        IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8)),
+    ]);
+
+    let addrs = resolver.resolve_endpoint(Endpoint::Host("example.com", 443)).wait().unwrap();
+    assert_eq!(addrs, vec![
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), 443),
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8)), 443),
+    ]);
+}
+
+#[test]
+fn test_resolve_endpoint_socket_addr_passthrough() {
+    use std::net::Ipv4Addr;
+
+    let resolver = StubResolver(vec![]);
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 1227);
+
+    let addrs = resolver.resolve_endpoint(Endpoint::SocketAddr(addr)).wait().unwrap();
+    assert_eq!(addrs, vec![addr]);
+}
+
+#[test]
+fn test_resolve_reverse_default_is_unsupported() {
+    use std::net::Ipv4Addr;
+
+    let resolver = StubResolver(vec![]);
+    assert!(resolver.resolve_reverse(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))).wait().is_err());
+}
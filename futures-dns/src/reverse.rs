@@ -0,0 +1,53 @@
+use std::ffi::CStr;
+use std::io;
+use std::mem;
+use std::net::IpAddr;
+use std::ptr;
+
+use futures::{BoxFuture, Future};
+use futures_cpupool::CpuPool;
+
+/// Resolves `ip` into its host name(s) using the platform's reverse
+/// DNS facility (`getnameinfo`), running the blocking lookup on `pool`.
+pub fn resolve_ip_addr(pool: &CpuPool, ip: IpAddr) -> BoxFuture<Vec<String>, io::Error> {
+    pool.execute(move || reverse_lookup(ip)).then(|res| {
+        // CpuFuture cannot fail unless it panics
+        res.unwrap()
+    }).boxed()
+}
+
+fn reverse_lookup(ip: IpAddr) -> io::Result<Vec<String>> {
+    let mut host = [0 as libc::c_char; libc::NI_MAXHOST as usize];
+
+    let ret = unsafe {
+        match ip {
+            IpAddr::V4(v4) => {
+                let mut sin: libc::sockaddr_in = mem::zeroed();
+                sin.sin_family = libc::AF_INET as libc::sa_family_t;
+                sin.sin_addr = libc::in_addr { s_addr: u32::from(v4).to_be() };
+                libc::getnameinfo(
+                    &sin as *const _ as *const libc::sockaddr,
+                    mem::size_of::<libc::sockaddr_in>() as libc::socklen_t,
+                    host.as_mut_ptr(), host.len() as libc::socklen_t,
+                    ptr::null_mut(), 0, 0)
+            }
+            IpAddr::V6(v6) => {
+                let mut sin6: libc::sockaddr_in6 = mem::zeroed();
+                sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+                sin6.sin6_addr = libc::in6_addr { s6_addr: v6.octets() };
+                libc::getnameinfo(
+                    &sin6 as *const _ as *const libc::sockaddr,
+                    mem::size_of::<libc::sockaddr_in6>() as libc::socklen_t,
+                    host.as_mut_ptr(), host.len() as libc::socklen_t,
+                    ptr::null_mut(), 0, 0)
+            }
+        }
+    };
+
+    if ret != 0 {
+        return Err(io::Error::new(io::ErrorKind::Other, "getnameinfo failed"));
+    }
+
+    let name = unsafe { CStr::from_ptr(host.as_ptr()) }.to_string_lossy().into_owned();
+    Ok(vec![name])
+}
@@ -0,0 +1,157 @@
+use std::io;
+use std::net::IpAddr;
+
+use futures::BoxFuture;
+
+use Resolver;
+
+/// A `Resolver` that dispatches to different backend resolvers based
+/// on the suffix of the host name being resolved.
+///
+/// This is useful for overriding resolution of specific namespaces
+/// (internal service discovery, test overrides, split-horizon DNS)
+/// while falling back to a default resolver for everything else.
+/// Build one with [`RouterResolver::builder`](struct.RouterResolver.html#method.builder).
+pub struct RouterResolver {
+    routes: Vec<(String, Box<Resolver + Send + Sync>)>,
+    fallthrough: Box<Resolver + Send + Sync>,
+}
+
+impl RouterResolver {
+    /// Starts building a `RouterResolver`.
+    pub fn builder() -> RouterResolverBuilder {
+        RouterResolverBuilder { routes: Vec::new() }
+    }
+
+    fn find(&self, host: &str) -> &Resolver {
+        let host = host.trim_right_matches('.');
+        let mut best: Option<&(String, Box<Resolver + Send + Sync>)> = None;
+
+        for route in &self.routes {
+            if suffix_matches(host, &route.0) {
+                let better = match best {
+                    Some(&(ref cur, _)) => route.0.len() > cur.len(),
+                    None => true,
+                };
+                if better {
+                    best = Some(route);
+                }
+            }
+        }
+
+        match best {
+            Some(&(_, ref resolver)) => &**resolver,
+            None => &*self.fallthrough,
+        }
+    }
+}
+
+impl Resolver for RouterResolver {
+    fn resolve(&self, host: &str) -> BoxFuture<Vec<IpAddr>, io::Error> {
+        self.find(host).resolve(host)
+    }
+
+    fn resolve_reverse(&self, ip: IpAddr) -> BoxFuture<Vec<String>, io::Error> {
+        // Reverse lookups carry no host name to route on, so they
+        // always go to the fallthrough resolver.
+        self.fallthrough.resolve_reverse(ip)
+    }
+}
+
+/// Builder for [`RouterResolver`](struct.RouterResolver.html).
+pub struct RouterResolverBuilder {
+    routes: Vec<(String, Box<Resolver + Send + Sync>)>,
+}
+
+impl RouterResolverBuilder {
+    /// Registers `resolver` as the backend for any host name ending
+    /// with `suffix`, matched on label boundaries (`example.com`
+    /// matches `api.example.com` but not `notexample.com`).
+    pub fn suffix<R>(mut self, suffix: &str, resolver: R) -> Self
+        where R: Resolver + Send + Sync + 'static
+    {
+        self.routes.push((suffix.trim_right_matches('.').to_owned(), Box::new(resolver)));
+        self
+    }
+
+    /// Sets the resolver used for host names that match no
+    /// registered suffix, and builds the `RouterResolver`.
+    pub fn fallthrough<R>(self, resolver: R) -> RouterResolver
+        where R: Resolver + Send + Sync + 'static
+    {
+        RouterResolver {
+            routes: self.routes,
+            fallthrough: Box::new(resolver),
+        }
+    }
+}
+
+/// Returns `true` if `host` is equal to `suffix`, or ends with
+/// `suffix` on a label (`.`-separated) boundary.
+fn suffix_matches(host: &str, suffix: &str) -> bool {
+    if host == suffix {
+        return true;
+    }
+    if host.len() > suffix.len() {
+        let boundary = host.len() - suffix.len();
+        if host.as_bytes()[boundary - 1] == b'.' && host[boundary..] == *suffix {
+            return true;
+        }
+    }
+    false
+}
+
+#[test]
+fn test_suffix_matches() {
+    assert!(suffix_matches("api.example.com", "example.com"));
+    assert!(suffix_matches("example.com", "example.com"));
+    assert!(!suffix_matches("notexample.com", "example.com"));
+    assert!(!suffix_matches("example.com", "api.example.com"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RouterResolver;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use futures::{future, BoxFuture, Future};
+    use std::io;
+    use Resolver;
+
+    struct TagResolver(IpAddr);
+
+    impl Resolver for TagResolver {
+        fn resolve(&self, _host: &str) -> BoxFuture<Vec<IpAddr>, io::Error> {
+            future::ok(vec![self.0]).boxed()
+        }
+    }
+
+    fn tag(d: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, d))
+    }
+
+    fn test_router() -> RouterResolver {
+        RouterResolver::builder()
+            .suffix("corp", TagResolver(tag(1)))
+            .suffix("internal.corp", TagResolver(tag(2)))
+            .fallthrough(TagResolver(tag(3)))
+    }
+
+    #[test]
+    fn test_longest_matching_suffix_wins() {
+        let router = test_router();
+        assert_eq!(router.resolve("api.internal.corp").wait().unwrap(), vec![tag(2)]);
+    }
+
+    #[test]
+    fn test_shorter_suffix_matches_when_longer_does_not() {
+        let router = test_router();
+        assert_eq!(router.resolve("example.corp").wait().unwrap(), vec![tag(1)]);
+    }
+
+    #[test]
+    fn test_fallthrough_when_nothing_matches() {
+        let router = test_router();
+        assert_eq!(router.resolve("example.com").wait().unwrap(), vec![tag(3)]);
+    }
+}
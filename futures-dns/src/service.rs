@@ -0,0 +1,92 @@
+//! An adapter exposing any `Resolver` as a `tower::Service`, for use
+//! with `tower`-based connection stacks (e.g. hyper's `HttpConnector`).
+//!
+//! Enabled by the `tower` feature.
+
+use std::fmt;
+use std::io;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::vec;
+
+use futures::{Async, BoxFuture, Future, Poll};
+use tower_service::Service;
+
+use Resolver;
+
+/// A validated host name, suitable for use as a `tower::Service`
+/// request. Rejects empty names, names carrying a port, and names
+/// with characters that can't appear in a host name.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Name(String);
+
+impl Name {
+    /// Returns the host name as a `&str`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Name {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::Other, "name must not be empty"));
+        }
+        if s.contains(':') {
+            return Err(io::Error::new(io::ErrorKind::Other, "name must not contain a port"));
+        }
+        if !s.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '.' || c == '_') {
+            return Err(io::Error::new(io::ErrorKind::Other, "name contains invalid characters"));
+        }
+        Ok(Name(s.to_owned()))
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Wraps a `Resolver` so it can be used as a `tower::Service<Name>`.
+pub struct ResolverService<R>(R);
+
+impl<R: Resolver> From<R> for ResolverService<R> {
+    fn from(resolver: R) -> Self {
+        ResolverService(resolver)
+    }
+}
+
+impl<R: Resolver> Service<Name> for ResolverService<R> {
+    type Response = vec::IntoIter<IpAddr>;
+    type Error = io::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        // Each request is carried out by the future the wrapped
+        // resolver returns; this service holds no capacity of its own,
+        // so it is always ready to accept a request.
+        Ok(Async::Ready(()))
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        self.0.resolve(name.as_str()).map(|ips| ips.into_iter()).boxed()
+    }
+}
+
+#[test]
+fn test_name_rejects_port() {
+    assert!(Name::from_str("example.com:80").is_err());
+}
+
+#[test]
+fn test_name_rejects_empty() {
+    assert!(Name::from_str("").is_err());
+}
+
+#[test]
+fn test_name_accepts_host() {
+    assert_eq!(Name::from_str("example.com").unwrap().as_str(), "example.com");
+}